@@ -1,79 +1,352 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use criteria_policy_base::{kubewarden_policy_sdk as kubewarden, settings::BaseSettings};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
+/// A single label (key or key/value pair) that failed validation, carrying
+/// enough detail for callers to act on the specific violation instead of
+/// pattern-matching an opaque message.
+#[derive(Debug, Clone, Error)]
+pub(crate) enum LabelValidationError {
+    #[error("{label} (prefix too long)")]
+    PrefixTooLong { label: String, len: usize },
+    #[error("{label} (name too long)")]
+    NameTooLong { label: String, len: usize },
+    #[error("{label} (key too long)")]
+    KeyTooLong { label: String, len: usize },
+    #[error("{label}")]
+    InvalidFormat { label: String },
+    #[error("{label}: {reason}")]
+    InvalidPrefixLabel { label: String, reason: String },
+    #[error("at least one label key/value pair must be configured")]
+    EmptyValueSet,
+    #[error("{key}={value} (invalid label value)")]
+    InvalidValue { key: String, value: String },
+    #[error("{key} (invalid value pattern '{pattern}': {source})")]
+    InvalidValuePattern {
+        key: String,
+        pattern: String,
+        #[source]
+        source: regex::Error,
+    },
+    #[error("{key} (invalid label values: {values})")]
+    InvalidOneOfValues { key: String, values: String },
+}
+
+// Joins a batch of errors into the single human-readable message returned
+// at the SDK boundary (`Validatable::validate`).
+fn join_errors(prefix: &str, errors: &[LabelValidationError]) -> String {
+    format!(
+        "{prefix}: {}",
+        errors
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct Settings {
+    #[serde(flatten)]
+    pub(crate) mode: SettingsMode,
+
+    /// Required key -> value to inject into `metadata.labels` via mutation
+    /// when the key is missing, or present with a different value. Lets
+    /// users configure an enforce-and-fix workflow instead of pure
+    /// rejection. Defaults are validated the same way as the `pairs` of a
+    /// `ContainsKeyValuePairs` settings mode.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) defaults: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub(crate) enum SettingsMode {
+    Keys(BaseSettings),
+    ContainsKeyValuePairs(ContainsKeyValuePairsSettings),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct ContainsKeyValuePairsSettings {
+    pub(crate) pairs: HashMap<String, ExpectedValue>,
+}
+
+// The value a label is expected to have. A plain string requires an exact
+// match, while `pattern`/`one_of` let the user constrain the value without
+// pinning it to a single literal.
 #[derive(Serialize, Deserialize, Debug, Clone)]
-pub(crate) struct Settings(pub(crate) BaseSettings);
+#[serde(untagged)]
+pub(crate) enum ExpectedValue {
+    Exact(String),
+    Pattern { pattern: String },
+    OneOf { one_of: HashSet<String> },
+}
 
 // It's not possible to use the Default in the derive macro because we cannot
 // set a #[default] attribute to enum item that is no unit enums.
 impl Default for Settings {
     fn default() -> Self {
-        Settings(BaseSettings::ContainsAnyOf {
-            values: HashSet::new(),
-        })
+        Settings {
+            mode: SettingsMode::Keys(BaseSettings::ContainsAnyOf {
+                values: HashSet::new(),
+            }),
+            defaults: HashMap::new(),
+        }
     }
 }
 
-// Regex used to validate the labels name:
-// - Optional DNS subdomain prefix (lowercase, digits, '-', '.'), ending with '/'
-// - Name segment: 1-63 chars, starts/ends with alphanumeric, allows '-', '_', '.' in between, case-insensitive for the name segment as per Kubernetes spec.
-const LABELS_NAME_REGEX: &str = r"^([a-z0-9]([-a-z0-9]*[a-z0-9])?(\.[a-z0-9]([-a-z0-9]*[a-z0-9])?)*/)?[a-zA-Z0-9]([a-zA-Z0-9_.-]{0,61}[a-zA-Z0-9])?$";
+// Regex used to validate the label *name* segment (the part after an
+// optional DNS subdomain prefix, or the whole key when there is no prefix):
+// 1-63 chars, starts/ends with an alphanumeric, allows '-', '_', '.' in
+// between, case-insensitive per the Kubernetes spec.
+const LABEL_NAME_REGEX: &str = r"^[a-zA-Z0-9]([a-zA-Z0-9_.-]{0,61}[a-zA-Z0-9])?$";
 
-impl kubewarden::settings::Validatable for Settings {
-    fn validate(&self) -> Result<(), String> {
-        // this will fail if the annotations key list is empty
-        self.0.validate()?;
+// Regex used to validate a label *value*, per the Kubernetes label-value rules:
+// empty, or at most 63 chars, starting and ending with an alphanumeric, with
+// '-', '_', '.' allowed in between.
+const LABEL_VALUE_REGEX: &str = r"^(([A-Za-z0-9][-A-Za-z0-9_.]*)?[A-Za-z0-9])?$";
+
+// Validates a single '.'-separated DNS label of a key's prefix, following
+// the same per-label approach used e.g. by trust-dns' `Name` parsing: each
+// label must be 1-63 lowercase alphanumeric/'-' characters, starting and
+// ending with an alphanumeric.
+fn validate_dns_label(label: &str, segment: &str, errors: &mut Vec<LabelValidationError>) {
+    if segment.is_empty() {
+        errors.push(LabelValidationError::InvalidPrefixLabel {
+            label: label.to_string(),
+            reason: "empty label in prefix".to_string(),
+        });
+        return;
+    }
+    if segment.len() > 63 {
+        errors.push(LabelValidationError::InvalidPrefixLabel {
+            label: label.to_string(),
+            reason: format!("label '{segment}' must be 1-63 characters long"),
+        });
+    }
+    if let Some(first) = segment.chars().next().filter(|c| !c.is_ascii_alphanumeric()) {
+        errors.push(LabelValidationError::InvalidPrefixLabel {
+            label: label.to_string(),
+            reason: format!("label '{segment}' must not start with '{first}'"),
+        });
+    }
+    if let Some(last) = segment.chars().last().filter(|c| !c.is_ascii_alphanumeric()) {
+        errors.push(LabelValidationError::InvalidPrefixLabel {
+            label: label.to_string(),
+            reason: format!("label '{segment}' must not end with '{last}'"),
+        });
+    }
+    if !segment
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        errors.push(LabelValidationError::InvalidPrefixLabel {
+            label: label.to_string(),
+            reason: format!("label '{segment}' must only contain lowercase alphanumerics and '-'"),
+        });
+    }
+}
 
-        let labels = self.0.values();
+fn validate_label_names(labels: &HashSet<String>) -> Result<(), Vec<LabelValidationError>> {
+    let label_name_regex = Regex::new(LABEL_NAME_REGEX).unwrap();
+    let mut errors = Vec::new();
 
-        // Validate that the annotations names are valid.
-        let labels_name_regex = Regex::new(LABELS_NAME_REGEX).unwrap();
-        let invalid_label: Vec<String> = labels
-            .iter()
-            .filter_map(|label| {
-                //     // Check total length
-                //     if label.len() > 253 {
-                //         return Some(format!("{label} (key too long)"));
-                //     }
-                //     if labels_name_regex.is_match(label) {
-                //         return None;
-                //     }
-                //     Some(label.to_string())
-                // })
-                // .collect();
-
-                if let Some(idx) = label.rfind('/') {
-                    let (prefix, name) = label.split_at(idx);
-                    let name = &name[1..]; // skip the '/'
-                    if prefix.len() > 253 {
-                        return Some(format!("{label} (prefix too long)"));
-                    }
-                    if name.len() > 63 {
-                        return Some(format!("{label} (name too long)"));
-                    }
-                    if label.len() > 253 {
-                        return Some(format!("{label} (key too long)"));
-                    }
-                } else if label.len() > 63 {
-                    return Some(format!("{label} (name too long)"));
+    for label in labels {
+        let name = match label.rfind('/') {
+            Some(idx) => {
+                let (prefix, rest) = label.split_at(idx);
+                let name = &rest[1..]; // skip the '/'
+
+                if label.len() > 253 {
+                    errors.push(LabelValidationError::KeyTooLong {
+                        label: label.clone(),
+                        len: label.len(),
+                    });
+                }
+                if prefix.len() > 253 {
+                    errors.push(LabelValidationError::PrefixTooLong {
+                        label: label.clone(),
+                        len: prefix.len(),
+                    });
                 }
-                if !labels_name_regex.is_match(label) {
-                    return Some(label.to_string());
+                for segment in prefix.split('.') {
+                    validate_dns_label(label, segment, &mut errors);
+                }
+
+                name
+            }
+            None => label.as_str(),
+        };
+
+        if name.len() > 63 {
+            errors.push(LabelValidationError::NameTooLong {
+                label: label.clone(),
+                len: name.len(),
+            });
+        } else if !label_name_regex.is_match(name) {
+            errors.push(LabelValidationError::InvalidFormat {
+                label: label.clone(),
+            });
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(())
+}
+
+fn validate_label_value(value: &str) -> bool {
+    if value.len() > 63 {
+        return false;
+    }
+    Regex::new(LABEL_VALUE_REGEX).unwrap().is_match(value)
+}
+
+fn validate_expected_values(
+    pairs: &HashMap<String, ExpectedValue>,
+) -> Result<(), Vec<LabelValidationError>> {
+    let errors: Vec<LabelValidationError> = pairs
+        .iter()
+        .filter_map(|(key, expected)| match expected {
+            ExpectedValue::Exact(value) => (!validate_label_value(value)).then(|| {
+                LabelValidationError::InvalidValue {
+                    key: key.clone(),
+                    value: value.clone(),
                 }
-                None
-            })
-            .collect();
-
-        if !invalid_label.is_empty() {
-            return Err(format!(
-                "Invalid annotation names: {}",
-                invalid_label.join(", "),
-            ));
+            }),
+            ExpectedValue::Pattern { pattern } => {
+                Regex::new(pattern)
+                    .err()
+                    .map(|source| LabelValidationError::InvalidValuePattern {
+                        key: key.clone(),
+                        pattern: pattern.clone(),
+                        source,
+                    })
+            }
+            ExpectedValue::OneOf { one_of } => {
+                let invalid_values: Vec<&String> =
+                    one_of.iter().filter(|v| !validate_label_value(v)).collect();
+                (!invalid_values.is_empty()).then(|| LabelValidationError::InvalidOneOfValues {
+                    key: key.clone(),
+                    values: invalid_values
+                        .iter()
+                        .map(|v| v.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                })
+            }
+        })
+        .collect();
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(())
+}
+
+impl ExpectedValue {
+    fn matches(&self, actual: &str) -> bool {
+        match self {
+            ExpectedValue::Exact(expected) => expected == actual,
+            ExpectedValue::Pattern { pattern } => Regex::new(pattern)
+                .map(|re| re.is_match(actual))
+                .unwrap_or(false),
+            ExpectedValue::OneOf { one_of } => one_of.contains(actual),
         }
+    }
+}
+
+impl ContainsKeyValuePairsSettings {
+    // Returns true when every configured key is present in `labels` and its
+    // value satisfies the configured expectation.
+    pub(crate) fn matches(&self, labels: &HashMap<String, String>) -> bool {
+        self.pairs.iter().all(|(key, expected)| {
+            labels
+                .get(key)
+                .is_some_and(|actual| expected.matches(actual))
+        })
+    }
+}
+
+// Defaults are just another set of key/value pairs: reuse the same name and
+// value validation as `ContainsKeyValuePairs` so an injected pair can never
+// itself be malformed.
+fn validate_defaults(defaults: &HashMap<String, String>) -> Result<(), Vec<LabelValidationError>> {
+    if defaults.is_empty() {
+        return Ok(());
+    }
+
+    let pairs: HashMap<String, ExpectedValue> = defaults
+        .iter()
+        .map(|(key, value)| (key.clone(), ExpectedValue::Exact(value.clone())))
+        .collect();
+    let keys: HashSet<String> = defaults.keys().cloned().collect();
+
+    let mut errors = validate_label_names(&keys).err().unwrap_or_default();
+    errors.extend(validate_expected_values(&pairs).err().unwrap_or_default());
+    if errors.is_empty() {
         Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+impl Settings {
+    // Structured counterpart of `Validatable::validate`: returns every
+    // violation found, rather than stopping at (or flattening) the first one.
+    pub(crate) fn validate_labels(&self) -> Result<(), Vec<LabelValidationError>> {
+        let mut errors = match &self.mode {
+            SettingsMode::Keys(base) => validate_label_names(base.values()).err().unwrap_or_default(),
+            SettingsMode::ContainsKeyValuePairs(settings) => {
+                if settings.pairs.is_empty() {
+                    vec![LabelValidationError::EmptyValueSet]
+                } else {
+                    let keys: HashSet<String> = settings.pairs.keys().cloned().collect();
+                    let mut errors = validate_label_names(&keys).err().unwrap_or_default();
+                    errors.extend(
+                        validate_expected_values(&settings.pairs)
+                            .err()
+                            .unwrap_or_default(),
+                    );
+                    errors
+                }
+            }
+        };
+        errors.extend(validate_defaults(&self.defaults).err().unwrap_or_default());
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // Computes the labels that must be patched into `metadata.labels` for
+    // every default to be satisfied: one entry per missing or mismatching
+    // key. Returns an empty map when the resource already satisfies all of
+    // them, meaning no mutation is needed.
+    pub(crate) fn missing_defaults(&self, labels: &HashMap<String, String>) -> HashMap<String, String> {
+        self.defaults
+            .iter()
+            .filter(|(key, value)| labels.get(key.as_str()) != Some(*value))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+impl kubewarden::settings::Validatable for Settings {
+    fn validate(&self) -> Result<(), String> {
+        if let SettingsMode::Keys(base) = &self.mode {
+            // this will fail if the annotations key list is empty
+            base.validate()?;
+        }
+
+        self.validate_labels()
+            .map_err(|errors| join_errors("Invalid labels", &errors))
     }
 }
 
@@ -93,8 +366,20 @@ mod tests {
     #[case::valid_multiple_prefix(vec!["foo.bar.baz/qux".to_string()], true)]
     #[case::valid_short(vec!["a/b".to_string()], true)]
     #[case::valid_alphanumeric(vec!["abc123".to_string()], true)]
-    #[case::valid_max_length(vec![format!("{}","a".repeat(243) + "/b")], true)]
-    #[case::valid_prefix_max_length(vec![format!("{}.com/abc", "a".repeat(243))], true)]
+    #[case::valid_multi_label_prefix(
+        vec![format!("{}.{}.{}/d", "a".repeat(63), "b".repeat(63), "c".repeat(63))],
+        true
+    )]
+    #[case::valid_prefix_near_max_length(
+        vec![format!(
+            "{}.{}.{}.{}/abc",
+            "a".repeat(63),
+            "b".repeat(63),
+            "c".repeat(63),
+            "d".repeat(57)
+        )],
+        true
+    )]
     #[case::valid_mixed(vec!["abc.def-ghi_jkl".to_string()], true)]
     // Invalid label keys
     #[case::invalid_empty(vec!["".to_string()], false)]
@@ -107,15 +392,270 @@ mod tests {
     #[case::invalid_at_symbol(vec!["example.com/my@label".to_string()], false)]
     #[case::invalid_uppercase_prefix(vec!["Example.com/my-label".to_string()], false)]
     #[case::invalid_double_dot_prefix(vec!["example..com/my-label".to_string()], false)]
+    #[case::invalid_trailing_dot_prefix(vec!["example.com./my-label".to_string()], false)]
     #[case::invalid_name_too_long(vec![format!("a{}", "b".repeat(63))], false)]
     #[case::invalid_prefix_too_long(vec![format!("{}.com/abc", "a".repeat(254))], false)]
+    #[case::invalid_dns_label_too_long(vec![format!("{}.com/abc", "a".repeat(64))], false)]
     fn test_validation(#[case] variables: Vec<String>, #[case] is_ok: bool) {
-        let settings = Settings(BaseSettings::ContainsAllOf {
-            values: variables
-                .iter()
-                .map(|v| v.to_string())
-                .collect::<HashSet<String>>(),
-        });
+        let settings = Settings {
+            mode: SettingsMode::Keys(BaseSettings::ContainsAllOf {
+                values: variables
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<HashSet<String>>(),
+            }),
+            defaults: HashMap::new(),
+        };
         assert_eq!(settings.validate().is_ok(), is_ok);
     }
+
+    #[rstest]
+    #[case::exact_match(ExpectedValue::Exact("production".to_string()), true)]
+    #[case::exact_empty_value(ExpectedValue::Exact("".to_string()), true)]
+    #[case::exact_invalid_value(ExpectedValue::Exact("-production".to_string()), false)]
+    #[case::exact_too_long(ExpectedValue::Exact("a".repeat(64)), false)]
+    #[case::pattern_valid(
+        ExpectedValue::Pattern {
+            pattern: "^(dev|staging|prod)$".to_string(),
+        },
+        true
+    )]
+    #[case::pattern_invalid_regex(
+        ExpectedValue::Pattern {
+            pattern: "(".to_string(),
+        },
+        false
+    )]
+    #[case::one_of_valid(
+        ExpectedValue::OneOf {
+            one_of: HashSet::from(["dev".to_string(), "staging".to_string(), "prod".to_string()]),
+        },
+        true
+    )]
+    #[case::one_of_invalid_value(
+        ExpectedValue::OneOf {
+            one_of: HashSet::from(["dev".to_string(), "-broken".to_string()]),
+        },
+        false
+    )]
+    fn test_contains_key_value_pairs_validation(
+        #[case] expected: ExpectedValue,
+        #[case] is_ok: bool,
+    ) {
+        let settings = Settings {
+            mode: SettingsMode::ContainsKeyValuePairs(ContainsKeyValuePairsSettings {
+                pairs: HashMap::from([("environment".to_string(), expected)]),
+            }),
+            defaults: HashMap::new(),
+        };
+        assert_eq!(settings.validate().is_ok(), is_ok);
+    }
+
+    #[test]
+    fn test_contains_key_value_pairs_requires_at_least_one_pair() {
+        let settings = Settings {
+            mode: SettingsMode::ContainsKeyValuePairs(ContainsKeyValuePairsSettings {
+                pairs: HashMap::new(),
+            }),
+            defaults: HashMap::new(),
+        };
+        assert!(settings.validate().is_err());
+    }
+
+    #[rstest]
+    #[case::exact_match(
+        ExpectedValue::Exact("production".to_string()),
+        "production",
+        true
+    )]
+    #[case::exact_mismatch(ExpectedValue::Exact("production".to_string()), "staging", false)]
+    #[case::pattern_match(
+        ExpectedValue::Pattern {
+            pattern: "^(dev|staging|prod)$".to_string(),
+        },
+        "prod",
+        true
+    )]
+    #[case::pattern_mismatch(
+        ExpectedValue::Pattern {
+            pattern: "^(dev|staging|prod)$".to_string(),
+        },
+        "production",
+        false
+    )]
+    #[case::one_of_match(
+        ExpectedValue::OneOf {
+            one_of: HashSet::from(["dev".to_string(), "prod".to_string()]),
+        },
+        "prod",
+        true
+    )]
+    #[case::one_of_mismatch(
+        ExpectedValue::OneOf {
+            one_of: HashSet::from(["dev".to_string(), "prod".to_string()]),
+        },
+        "staging",
+        false
+    )]
+    fn test_expected_value_matches(
+        #[case] expected: ExpectedValue,
+        #[case] actual: &str,
+        #[case] matches: bool,
+    ) {
+        assert_eq!(expected.matches(actual), matches);
+    }
+
+    #[rstest]
+    #[case::name_too_long(
+        vec![format!("a{}", "b".repeat(63))],
+        |e: &LabelValidationError| matches!(e, LabelValidationError::NameTooLong { .. })
+    )]
+    #[case::prefix_too_long(
+        vec![format!("{}.com/abc", "a".repeat(254))],
+        |e: &LabelValidationError| matches!(e, LabelValidationError::PrefixTooLong { .. })
+    )]
+    #[case::invalid_format(
+        vec!["example.com/my label".to_string()],
+        |e: &LabelValidationError| matches!(e, LabelValidationError::InvalidFormat { .. })
+    )]
+    #[case::empty_label_in_prefix(
+        vec!["example..com/my-label".to_string()],
+        |e: &LabelValidationError| matches!(
+            e,
+            LabelValidationError::InvalidPrefixLabel { reason, .. } if reason == "empty label in prefix"
+        )
+    )]
+    #[case::trailing_dot_in_prefix(
+        vec!["example.com./my-label".to_string()],
+        |e: &LabelValidationError| matches!(
+            e,
+            LabelValidationError::InvalidPrefixLabel { reason, .. } if reason == "empty label in prefix"
+        )
+    )]
+    #[case::dns_label_ends_with_dash(
+        vec!["example.com-/my-label".to_string()],
+        |e: &LabelValidationError| matches!(
+            e,
+            LabelValidationError::InvalidPrefixLabel { reason, .. } if reason == "label 'com-' must not end with '-'"
+        )
+    )]
+    fn test_validate_labels_reports_specific_violation(
+        #[case] variables: Vec<String>,
+        #[case] expected_variant: fn(&LabelValidationError) -> bool,
+    ) {
+        let settings = Settings {
+            mode: SettingsMode::Keys(BaseSettings::ContainsAllOf {
+                values: variables.into_iter().collect::<HashSet<String>>(),
+            }),
+            defaults: HashMap::new(),
+        };
+        let errors = settings.validate_labels().expect_err("expected a violation");
+        assert!(errors.iter().any(expected_variant));
+    }
+
+    #[test]
+    fn test_validate_labels_reports_empty_value_set() {
+        let settings = Settings {
+            mode: SettingsMode::ContainsKeyValuePairs(ContainsKeyValuePairsSettings {
+                pairs: HashMap::new(),
+            }),
+            defaults: HashMap::new(),
+        };
+        let errors = settings.validate_labels().expect_err("expected a violation");
+        assert!(matches!(errors.as_slice(), [LabelValidationError::EmptyValueSet]));
+    }
+
+    #[test]
+    fn test_validate_labels_reports_invalid_value_pattern() {
+        let settings = Settings {
+            mode: SettingsMode::ContainsKeyValuePairs(ContainsKeyValuePairsSettings {
+                pairs: HashMap::from([(
+                    "environment".to_string(),
+                    ExpectedValue::Pattern {
+                        pattern: "(".to_string(),
+                    },
+                )]),
+            }),
+            defaults: HashMap::new(),
+        };
+        let errors = settings.validate_labels().expect_err("expected a violation");
+        assert!(errors
+            .iter()
+            .any(|e| matches!(e, LabelValidationError::InvalidValuePattern { .. })));
+    }
+
+    #[test]
+    fn test_contains_key_value_pairs_matches_missing_key() {
+        let settings = ContainsKeyValuePairsSettings {
+            pairs: HashMap::from([(
+                "environment".to_string(),
+                ExpectedValue::Exact("production".to_string()),
+            )]),
+        };
+        let labels = HashMap::from([("team".to_string(), "platform".to_string())]);
+        assert!(!settings.matches(&labels));
+    }
+
+    #[rstest]
+    #[case::valid_default(HashMap::from([("environment".to_string(), "production".to_string())]), true)]
+    #[case::invalid_default_key(HashMap::from([("-environment".to_string(), "production".to_string())]), false)]
+    #[case::invalid_default_value(HashMap::from([("environment".to_string(), "-production".to_string())]), false)]
+    fn test_validate_defaults(#[case] defaults: HashMap<String, String>, #[case] is_ok: bool) {
+        let settings = Settings {
+            mode: SettingsMode::Keys(BaseSettings::ContainsAnyOf {
+                values: HashSet::new(),
+            }),
+            defaults,
+        };
+        assert_eq!(settings.validate_labels().is_ok(), is_ok);
+    }
+
+    #[test]
+    fn test_missing_defaults_injects_absent_key() {
+        let settings = Settings {
+            mode: SettingsMode::Keys(BaseSettings::ContainsAnyOf {
+                values: HashSet::new(),
+            }),
+            defaults: HashMap::from([("environment".to_string(), "production".to_string())]),
+        };
+        let labels = HashMap::from([("team".to_string(), "platform".to_string())]);
+
+        let patch = settings.missing_defaults(&labels);
+
+        assert_eq!(
+            patch,
+            HashMap::from([("environment".to_string(), "production".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_missing_defaults_normalizes_mismatching_value() {
+        let settings = Settings {
+            mode: SettingsMode::Keys(BaseSettings::ContainsAnyOf {
+                values: HashSet::new(),
+            }),
+            defaults: HashMap::from([("environment".to_string(), "production".to_string())]),
+        };
+        let labels = HashMap::from([("environment".to_string(), "staging".to_string())]);
+
+        let patch = settings.missing_defaults(&labels);
+
+        assert_eq!(
+            patch,
+            HashMap::from([("environment".to_string(), "production".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_missing_defaults_is_empty_when_already_satisfied() {
+        let settings = Settings {
+            mode: SettingsMode::Keys(BaseSettings::ContainsAnyOf {
+                values: HashSet::new(),
+            }),
+            defaults: HashMap::from([("environment".to_string(), "production".to_string())]),
+        };
+        let labels = HashMap::from([("environment".to_string(), "production".to_string())]);
+
+        assert!(settings.missing_defaults(&labels).is_empty());
+    }
 }